@@ -1,5 +1,8 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::thread;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Die {
@@ -22,6 +25,37 @@ impl Die {
     ];
 }
 
+// How duplicate dice within a column contribute to its score: the crate's
+// own squared-pair formula (see `HandExplicit::score`) versus the classic
+// "multiply each of the N matching dice by N" variant.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ScoringPolicy {
+    SquaredPair,
+    Multiplied,
+}
+
+// Parameterizes the board dimensions and scoring policy that the rest of
+// the crate otherwise hardcodes to a 3-tall column of six-sided dice with
+// the squared-pair formula. `faces` and `column_height` are bounded by the
+// fixed-size `Die::VALUES` array and `HandExplicit`'s three slots
+// respectively, so they can shrink the classic game but not grow it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct RuleSet {
+    faces: u8,
+    column_height: u8,
+    scoring: ScoringPolicy,
+}
+
+impl RuleSet {
+    const fn classic() -> RuleSet {
+        RuleSet {
+            faces: 6,
+            column_height: 3,
+            scoring: ScoringPolicy::SquaredPair,
+        }
+    }
+}
+
 trait Hand {
     fn die_1(&self) -> Option<Die>;
 
@@ -111,6 +145,191 @@ impl HandExplicit {
     fn val_3(&self) -> u8 {
         Self::val(self.die_3)
     }
+
+    fn push(&self, die: Die) -> HandExplicit {
+        self.push_with(die, &RuleSet::classic())
+    }
+
+    // `push`, but the fullness check respects `ruleset`'s column height
+    // instead of assuming the classic 3-tall column.
+    fn push_with(&self, die: Die, ruleset: &RuleSet) -> HandExplicit {
+        assert!(!self.is_full_with(ruleset), "cannot push a die onto a full hand");
+        let mut dice: Vec<Die> = [self.die_1, self.die_2, self.die_3]
+            .into_iter()
+            .flatten()
+            .collect();
+        dice.push(die);
+        dice.sort_unstable();
+        HandExplicit {
+            die_1: dice.first().copied(),
+            die_2: dice.get(1).copied(),
+            die_3: dice.get(2).copied(),
+        }
+    }
+
+    fn remove_all(&self, die: Die) -> HandExplicit {
+        let mut dice = [self.die_1, self.die_2, self.die_3]
+            .into_iter()
+            .flatten()
+            .filter(|d| *d != die);
+        HandExplicit {
+            die_1: dice.next(),
+            die_2: dice.next(),
+            die_3: dice.next(),
+        }
+    }
+
+    // Dense rank of this hand among all sorted hands reachable under the
+    // classic ruleset (84 of them); see `rank_with`.
+    fn rank(&self) -> u16 {
+        self.rank_with(&RuleSet::classic())
+    }
+
+    // `rank`, generalized to `ruleset`'s face count and column height: an
+    // offset for the hand's length plus the combinations-with-repetition
+    // rank of its dice within that length. This is the inverse of
+    // `unrank_with` and lets `HandPairs` index hands without hashing.
+    fn rank_with(&self, ruleset: &RuleSet) -> u16 {
+        let len = self.len();
+        let offset = hands_before_len_with(ruleset, len);
+        let ys: Vec<u32> = [self.die_1, self.die_2, self.die_3]
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(|(i, d)| (d as u32 - 1) + i as u32)
+            .collect();
+        (offset + combination_rank(&ys)) as u16
+    }
+
+    fn unrank(ix: u16) -> HandExplicit {
+        Self::unrank_with(&RuleSet::classic(), ix)
+    }
+
+    fn unrank_with(ruleset: &RuleSet, ix: u16) -> HandExplicit {
+        let mut remaining = ix as u32;
+        let mut len = 0u8;
+        loop {
+            let count = hands_of_len_with(ruleset, len);
+            if remaining < count {
+                break;
+            }
+            remaining -= count;
+            len += 1;
+        }
+        let ys = combination_unrank(remaining, len as u32);
+        let dice: Vec<Die> = ys
+            .iter()
+            .enumerate()
+            .map(|(i, &y)| Die::VALUES[(y - i as u32) as usize])
+            .collect();
+        match len {
+            0 => HandExplicit::empty(),
+            1 => HandExplicit::new1(dice[0]),
+            2 => HandExplicit::new2(dice[0], dice[1]),
+            3 => HandExplicit::new(dice[0], dice[1], dice[2]),
+            _ => unreachable!("hand length out of range: {}", len),
+        }
+    }
+
+    // Whether this hand is full under `ruleset`'s column height.
+    fn is_full_with(&self, ruleset: &RuleSet) -> bool {
+        self.len() >= ruleset.column_height
+    }
+
+    // `score`, generalized to `ruleset`'s face count and duplicate-scoring
+    // policy. `SquaredPair` matches the classic-ruleset formula: the sum of
+    // face values plus, for every pair of dice sharing a face, that face
+    // squared. `Multiplied` instead scores each of the N dice sharing a
+    // face as `face * N`, the traditional Knucklebones rule.
+    fn score_with(&self, ruleset: &RuleSet) -> u8 {
+        let dice: Vec<u8> = [self.die_1, self.die_2, self.die_3]
+            .into_iter()
+            .flatten()
+            .map(|d| d as u8)
+            .collect();
+        let mut total = 0u16;
+        for face in 1..=ruleset.faces {
+            let count = dice.iter().filter(|&&d| d == face).count() as u16;
+            match ruleset.scoring {
+                ScoringPolicy::SquaredPair => {
+                    total += count * face as u16;
+                    total += (count * (count.saturating_sub(1)) / 2) * face as u16 * face as u16;
+                }
+                ScoringPolicy::Multiplied => {
+                    total += count * count * face as u16;
+                }
+            }
+        }
+        total as u8
+    }
+
+    // Serializes as the sorted face list, e.g. `(13)` -> "[1,3]".
+    fn to_json(&self) -> String {
+        let faces: Vec<String> = [self.die_1, self.die_2, self.die_3]
+            .into_iter()
+            .flatten()
+            .map(|d| (d as u8).to_string())
+            .collect();
+        format!("[{}]", faces.join(","))
+    }
+}
+
+// Binomial coefficient, 0 if k > n.
+fn choose(n: u32, k: u32) -> u32 {
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    let mut result: u64 = 1;
+    for i in 1..=k {
+        result = result * (n - k + i) as u64 / i as u64;
+    }
+    result as u32
+}
+
+// Number of non-decreasing `len`-tuples over `ruleset`'s faces (stars and
+// bars).
+fn hands_of_len_with(ruleset: &RuleSet, len: u8) -> u32 {
+    choose(ruleset.faces as u32 - 1 + len as u32, len as u32)
+}
+
+// Number of hands with a strictly shorter length than `len`, under
+// `ruleset`'s face count.
+fn hands_before_len_with(ruleset: &RuleSet, len: u8) -> u32 {
+    (0..len).map(|l| hands_of_len_with(ruleset, l)).sum()
+}
+
+// Total number of distinct hands reachable under `ruleset` (all lengths
+// 0..=ruleset.column_height).
+fn hand_count_with(ruleset: &RuleSet) -> u32 {
+    hands_before_len_with(ruleset, ruleset.column_height + 1)
+}
+
+// Combinatorial-number-system rank of a strictly increasing sequence `ys`:
+// sum of choose(ys[i], i + 1). This is the standard bijection between
+// combinations-with-repetition and plain combinations (shift each sorted
+// value by its position to make it strictly increasing).
+fn combination_rank(ys: &[u32]) -> u32 {
+    ys.iter()
+        .enumerate()
+        .map(|(i, &y)| choose(y, i as u32 + 1))
+        .sum()
+}
+
+// Inverse of `combination_rank` for a sequence of length `k`.
+fn combination_unrank(mut rank: u32, k: u32) -> Vec<u32> {
+    let mut ys = vec![0u32; k as usize];
+    let mut upper_bound = u32::MAX;
+    for j in (1..=k).rev() {
+        let mut y = j - 1;
+        while y + 1 < upper_bound && choose(y + 1, j) <= rank {
+            y += 1;
+        }
+        rank -= choose(y, j);
+        upper_bound = y;
+        ys[(j - 1) as usize] = y;
+    }
+    ys
 }
 
 impl Hand for HandExplicit {
@@ -131,7 +350,7 @@ impl Hand for HandExplicit {
     }
 
     fn is_full(&self) -> bool {
-        self.die_3.is_some()
+        self.is_full_with(&RuleSet::classic())
     }
 
     fn len(&self) -> u8 {
@@ -147,23 +366,7 @@ impl Hand for HandExplicit {
     }
 
     fn score(&self) -> u8 {
-        let abc = self.val_1() + self.val_2() + self.val_3();
-        let ab = if self.die_1 == self.die_2 {
-            self.val_1() * self.val_1()
-        } else {
-            0
-        };
-        let bc = if self.die_2 == self.die_3 {
-            self.val_2() * self.val_2()
-        } else {
-            0
-        };
-        let ac = if self.die_1 == self.die_3 {
-            self.val_1() * self.val_1()
-        } else {
-            0
-        };
-        abc + ab + bc + ac
+        self.score_with(&RuleSet::classic())
     }
 }
 
@@ -196,38 +399,149 @@ impl fmt::Display for Die {
     }
 }
 
+// Number of hands that partner with `hand_1` to form a legal
+// (non-overlapping) column pair, under
+// `ruleset`'s face count and column height.
+fn partner_count_with(hand_1: &HandExplicit, ruleset: &RuleSet) -> u32 {
+    (0..hand_count_with(ruleset))
+        .filter(|&r2| !overlaps(hand_1, &HandExplicit::unrank_with(ruleset, r2 as u16)))
+        .count() as u32
+}
+
+// The faces `hand_1` doesn't hold, in ascending order. Any hand built only
+// from these faces can't `overlap` `hand_1`, and this is the alphabet
+// `rank_restricted`/`unrank_restricted` rank non-overlapping partners
+// against.
+fn allowed_faces(hand_1: &HandExplicit, ruleset: &RuleSet) -> Vec<Die> {
+    Die::VALUES[..ruleset.faces as usize]
+        .iter()
+        .copied()
+        .filter(|&d| !hand_1.has(d))
+        .collect()
+}
+
+// `hands_of_len_with`, but over an arbitrary alphabet size instead of
+// `ruleset.faces` (stars and bars over `n_alphabet` values, or exactly the
+// empty hand when there are no allowed faces left at all).
+fn combos_of_len(n_alphabet: u32, len: u32) -> u32 {
+    if n_alphabet == 0 {
+        return if len == 0 { 1 } else { 0 };
+    }
+    choose(n_alphabet - 1 + len, len)
+}
+
+// `rank_with`, restricted to hands built only from `allowed`'s faces: the
+// dense rank of `hand` among every hand drawable from that alphabet alone.
+// This is the rank `hand` has among `hand_1`'s non-overlapping partners when
+// `allowed` is `allowed_faces(hand_1, ruleset)`, computed directly instead of
+// by scanning and filtering every hand below it.
+fn rank_restricted(allowed: &[Die], hand: &HandExplicit) -> u32 {
+    let len = hand.len() as u32;
+    let offset: u32 = (0..len).map(|l| combos_of_len(allowed.len() as u32, l)).sum();
+    let ys: Vec<u32> = [hand.die_1, hand.die_2, hand.die_3]
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, d)| allowed.iter().position(|&a| a == d).unwrap() as u32 + i as u32)
+        .collect();
+    offset + combination_rank(&ys)
+}
+
+// Inverse of `rank_restricted`: the hand at rank `rank` among every hand
+// drawable from `allowed`'s faces alone.
+fn unrank_restricted(allowed: &[Die], rank: u32) -> HandExplicit {
+    let n_allowed = allowed.len() as u32;
+    let mut remaining = rank;
+    let mut len = 0u32;
+    loop {
+        let count = combos_of_len(n_allowed, len);
+        if remaining < count {
+            break;
+        }
+        remaining -= count;
+        len += 1;
+    }
+    let ys = combination_unrank(remaining, len);
+    let dice: Vec<Die> = ys
+        .iter()
+        .enumerate()
+        .map(|(i, &y)| allowed[(y - i as u32) as usize])
+        .collect();
+    match len {
+        0 => HandExplicit::empty(),
+        1 => HandExplicit::new1(dice[0]),
+        2 => HandExplicit::new2(dice[0], dice[1]),
+        3 => HandExplicit::new(dice[0], dice[1], dice[2]),
+        _ => unreachable!("hand length out of range: {}", len),
+    }
+}
+
+// `HandPairs` used to be a pair of `HashMap`s built by enumerating
+// `hand_pairs()`; it is now a thin facade over `HandExplicit::rank`/`unrank`
+// arithmetic under its `ruleset`, so lookups are direct index math rather
+// than hashing. `State`, `Solver`, and the rest of the engine never see a
+// `RuleSet` directly — they take a `&HandPairs` everywhere and read its
+// `ruleset()` instead.
+//
+// `partner_count_prefix_sums[r]` is the total number of valid pairs whose
+// first hand has rank < r, so lookups don't re-scan `partner_count_with` (itself
+// an O(hand_count) walk) from scratch for every rank below the target, the
+// way a naive loop would. It's built once, in `new_with`, from at most
+// `hand_count_with(ruleset) + 1` entries (85 for the classic ruleset).
 struct HandPairs {
-    ix_to_hand: HashMap<u16, (HandExplicit, HandExplicit)>,
-    hand_to_ix: HashMap<(HandExplicit, HandExplicit), u16>,
+    ruleset: RuleSet,
+    partner_count_prefix_sums: Vec<u32>,
 }
 
 impl HandPairs {
     fn new() -> HandPairs {
-        let hands = hands();
-        let hand_pairs = hand_pairs(hands);
-        let mut ix_to_hand = HashMap::with_capacity(hand_pairs.len());
-        let mut hand_to_ix = HashMap::with_capacity(hand_pairs.len());
-        let mut ix: u16 = 0;
-        for hand_pair in hand_pairs.iter() {
-            ix_to_hand.insert(ix, *hand_pair);
-            hand_to_ix.insert(*hand_pair, ix);
-            ix += 1;
+        HandPairs::new_with(RuleSet::classic())
+    }
+
+    fn new_with(ruleset: RuleSet) -> HandPairs {
+        let hand_count = hand_count_with(&ruleset);
+        let mut partner_count_prefix_sums = Vec::with_capacity(hand_count as usize + 1);
+        partner_count_prefix_sums.push(0);
+        for r1 in 0..hand_count {
+            let hand_1 = HandExplicit::unrank_with(&ruleset, r1 as u16);
+            let prior = *partner_count_prefix_sums.last().unwrap();
+            partner_count_prefix_sums.push(prior + partner_count_with(&hand_1, &ruleset));
         }
         HandPairs {
-            ix_to_hand,
-            hand_to_ix,
+            ruleset,
+            partner_count_prefix_sums,
         }
     }
 
-    fn get_by_index(&self, ix: u16) -> &(HandExplicit, HandExplicit) {
-        self.ix_to_hand.get(&ix).unwrap()
+    fn ruleset(&self) -> RuleSet {
+        self.ruleset
+    }
+
+    fn get_by_index(&self, ix: u16) -> (HandExplicit, HandExplicit) {
+        let ix = ix as u32;
+        assert!(
+            ix < *self.partner_count_prefix_sums.last().unwrap(),
+            "hand pair index out of range: {}",
+            ix
+        );
+        let r1 = self.partner_count_prefix_sums.partition_point(|&sum| sum <= ix) - 1;
+        let remaining = ix - self.partner_count_prefix_sums[r1];
+        let hand_1 = HandExplicit::unrank_with(&self.ruleset, r1 as u16);
+        let allowed = allowed_faces(&hand_1, &self.ruleset);
+        let hand_2 = unrank_restricted(&allowed, remaining);
+        (hand_1, hand_2)
     }
 
     fn get_by_hand(&self, hand_pair: &(HandExplicit, HandExplicit)) -> u16 {
-        *self.hand_to_ix.get(hand_pair).unwrap()
+        let (hand_1, hand_2) = hand_pair;
+        let target_r1 = hand_1.rank_with(&self.ruleset) as usize;
+        let allowed = allowed_faces(hand_1, &self.ruleset);
+        let ix = self.partner_count_prefix_sums[target_r1] + rank_restricted(&allowed, hand_2);
+        ix as u16
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 struct State {
     column_1: u16,
     column_2: u16, // >= column_2
@@ -258,13 +572,13 @@ impl State {
         }
     }
 
-    fn hands<'a>(
-        &'a self,
-        hand_pairs: &'a HandPairs,
+    fn hands(
+        &self,
+        hand_pairs: &HandPairs,
     ) -> (
-        &(HandExplicit, HandExplicit),
-        &(HandExplicit, HandExplicit),
-        &(HandExplicit, HandExplicit),
+        (HandExplicit, HandExplicit),
+        (HandExplicit, HandExplicit),
+        (HandExplicit, HandExplicit),
     ) {
         (
             hand_pairs.get_by_index(self.column_1),
@@ -279,48 +593,420 @@ impl State {
     }
 
     fn is_done(&self, hand_pairs: &HandPairs) -> bool {
+        let ruleset = hand_pairs.ruleset();
         let (c1, c2, c3) = self.hands(hand_pairs);
-        (c1.0.is_full() && c2.0.is_full() && c3.0.is_full())
-            || (c1.1.is_full() && c2.1.is_full() && c3.1.is_full())
+        (c1.0.is_full_with(&ruleset) && c2.0.is_full_with(&ruleset) && c3.0.is_full_with(&ruleset))
+            || (c1.1.is_full_with(&ruleset)
+                && c2.1.is_full_with(&ruleset)
+                && c3.1.is_full_with(&ruleset))
     }
 
     fn num_choices(&self, hand_pairs: &HandPairs) -> u8 {
+        let ruleset = hand_pairs.ruleset();
         let (c1, c2, c3) = self.hands(hand_pairs);
         let mut x = 0;
-        if !c1.0.is_full() {
+        if !c1.0.is_full_with(&ruleset) {
             x += 1;
         }
-        if !c2.0.is_full() {
+        if !c2.0.is_full_with(&ruleset) {
             x += 1;
         }
-        if !c3.0.is_full() {
+        if !c3.0.is_full_with(&ruleset) {
             x += 1;
         }
         x
     }
+
+    // Drops `die` onto `player`'s side of `column_ix` (0, 1 or 2, indexing
+    // self.column_1/column_2/column_3), then applies the destruction rule:
+    // every die on the opponent's side of that same column matching `die`
+    // is removed and the remainder compacted.
+    fn place(&self, hand_pairs: &HandPairs, column_ix: u8, die: Die, player: u8) -> State {
+        let ruleset = hand_pairs.ruleset();
+        let (c1, c2, c3) = self.hands(hand_pairs);
+        let mut columns = [c1, c2, c3];
+        let (mover, opponent) = &mut columns[column_ix as usize];
+        let (mover, opponent) = if player == 0 {
+            (mover.push_with(die, &ruleset), opponent.remove_all(die))
+        } else {
+            (opponent.push_with(die, &ruleset), mover.remove_all(die))
+        };
+        columns[column_ix as usize] = if player == 0 {
+            (mover, opponent)
+        } else {
+            (opponent, mover)
+        };
+        State::new(hand_pairs, &columns[0], &columns[1], &columns[2])
+    }
+
+    // Non-full columns (0, 1 or 2) that `player` may still drop a die into.
+    fn legal_moves(&self, hand_pairs: &HandPairs, player: u8) -> impl Iterator<Item = u8> {
+        let ruleset = hand_pairs.ruleset();
+        let (c1, c2, c3) = self.hands(hand_pairs);
+        let full = [c1, c2, c3].map(|c| {
+            if player == 0 {
+                c.0.is_full_with(&ruleset)
+            } else {
+                c.1.is_full_with(&ruleset)
+            }
+        });
+        (0u8..3).filter(move |&ix| !full[ix as usize])
+    }
+
+    // The player to move on move number `move_number` (0-indexed): player 0
+    // moves first, then players alternate. This can't be derived from the
+    // board occupancy the way `is_done`/`num_choices` are: the destruction
+    // rule can remove an odd number of the opponent's dice in a single
+    // move, which would otherwise flip the total-dice-placed parity this
+    // used to be computed from. Callers must track `move_number` (a plain
+    // move count) themselves, since `State` has no move history.
+    fn turn(move_number: u32) -> u8 {
+        (move_number % 2) as u8
+    }
+
+    // Serializes as the three canonical column-pair ranks, e.g. "[0,7,42]".
+    fn to_json(&self) -> String {
+        format!("[{},{},{}]", self.column_1, self.column_2, self.column_3)
+    }
 }
 
+// The outcome of rolling `die` in a given state: the column that maximizes
+// the active player's expected signed score, and that expected value.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct MoveValue {
+    best_column: u8,
+    expected_value: f64,
+}
+
+// How tightly `Solver::solve` converges its value iteration, and how many
+// sweeps it will make before giving up.
+const SOLVE_EPSILON: f64 = 1e-9;
+const SOLVE_MAX_SWEEPS: u32 = 10_000;
+
+// Solves Knucklebones by expectiminimax value iteration: chance nodes
+// average a die roll, decision nodes pick the column maximizing the active
+// player's value after negating the opponent's. `solve` sweeps the Bellman
+// update over every state until values stop moving, rather than a single
+// retrograde pass, since the destruction rule can return a board to an
+// identical canonical `State` and so the dependency graph isn't acyclic.
+struct Solver<'a> {
+    hand_pairs: &'a HandPairs,
+    values: HashMap<State, f64>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(hand_pairs: &'a HandPairs) -> Solver<'a> {
+        Solver {
+            hand_pairs,
+            values: HashMap::new(),
+        }
+    }
+
+    fn solve(&mut self, states: &[State]) {
+        for state in states {
+            self.values.entry(*state).or_insert(0.0);
+        }
+        for _ in 0..SOLVE_MAX_SWEEPS {
+            let mut max_delta = 0.0_f64;
+            for state in states {
+                if state.is_done(self.hand_pairs) {
+                    continue;
+                }
+                let updated = self.chance_value(state);
+                let previous = *self.values.get(state).unwrap();
+                max_delta = f64::max(max_delta, (updated - previous).abs());
+                self.values.insert(*state, updated);
+            }
+            if max_delta < SOLVE_EPSILON {
+                break;
+            }
+        }
+    }
+
+    // Same convergence as `solve`, but each sweep splits `states` into one
+    // contiguous slice per worker thread. Every thread reads the values
+    // left by the previous sweep (a Jacobi update, not Gauss-Seidel), so
+    // the slices can be computed fully in parallel; only merging each
+    // sweep's results back into `self.values` is sequential. Kept
+    // alongside `solve` so the single-threaded path stays available for
+    // deterministic debugging.
+    fn solve_parallel(&mut self, states: &[State]) {
+        for state in states {
+            self.values.entry(*state).or_insert(0.0);
+        }
+        let thread_count = available_parallelism();
+        let chunk_size = states.len().max(1).div_ceil(thread_count).max(1);
+        for _ in 0..SOLVE_MAX_SWEEPS {
+            let solver = &*self;
+            let updates: Vec<(State, f64)> = thread::scope(|scope| {
+                states
+                    .chunks(chunk_size)
+                    .map(|chunk| {
+                        scope.spawn(move || {
+                            chunk
+                                .iter()
+                                .filter(|state| !state.is_done(solver.hand_pairs))
+                                .map(|state| (*state, solver.chance_value(state)))
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .flat_map(|handle| handle.join().expect("worker thread panicked"))
+                    .collect()
+            });
+            let mut max_delta = 0.0_f64;
+            for (state, updated) in updates {
+                let previous = *self.values.get(&state).unwrap();
+                max_delta = f64::max(max_delta, (updated - previous).abs());
+                self.values.insert(state, updated);
+            }
+            if max_delta < SOLVE_EPSILON {
+                break;
+            }
+        }
+    }
+
+    // The active player's (side 0's) expected signed score from `state`,
+    // read from the table `solve` filled in (or computed directly for a
+    // terminal state, which `solve` never updates).
+    fn value(&self, state: &State) -> f64 {
+        if state.is_done(self.hand_pairs) {
+            let ruleset = self.hand_pairs.ruleset();
+            let (c1, c2, c3) = state.hands(self.hand_pairs);
+            let score_0 = c1.0.score_with(&ruleset) + c2.0.score_with(&ruleset) + c3.0.score_with(&ruleset);
+            let score_1 = c1.1.score_with(&ruleset) + c2.1.score_with(&ruleset) + c3.1.score_with(&ruleset);
+            score_0 as f64 - score_1 as f64
+        } else {
+            *self.values.get(state).unwrap_or(&0.0)
+        }
+    }
+
+    // The value of the chance node that rolls a die (over `ruleset`'s
+    // faces) and then decides.
+    fn chance_value(&self, state: &State) -> f64 {
+        let faces = &Die::VALUES[..self.hand_pairs.ruleset().faces as usize];
+        faces
+            .iter()
+            .map(|die| self.decision(state, *die).expected_value)
+            .sum::<f64>()
+            / faces.len() as f64
+    }
+
+    // The best column for the active player (side 0) to drop `die` into
+    // from `state`, and the resulting expected signed score.
+    fn decision(&self, state: &State, die: Die) -> MoveValue {
+        let mut best = MoveValue {
+            best_column: 0,
+            expected_value: f64::NEG_INFINITY,
+        };
+        for column in state.legal_moves(self.hand_pairs, 0) {
+            // It's the opponent's turn in the child, so reverse perspective
+            // (they become side 0) and negate their value back into ours.
+            let child = state.place(self.hand_pairs, column, die, 0).reverse(self.hand_pairs);
+            let expected_value = -self.value(&child);
+            if expected_value > best.expected_value {
+                best = MoveValue {
+                    best_column: column,
+                    expected_value,
+                };
+            }
+        }
+        best
+    }
+
+    fn best_move(&self, state: &State, die: Die) -> u8 {
+        self.decision(state, die).best_column
+    }
+
+    fn expected_value(&self, state: &State, die: Die) -> f64 {
+        self.decision(state, die).expected_value
+    }
+}
+
+// `table <faces> <column_height> [--multiplied] [--parallel] [out_path]`:
+// dimensions are required (not defaulted to classic) since the classic
+// ruleset's state space is too large for `emit_table` to solve in memory.
 fn main() {
-    let hands = hands();
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("table") {
+        let usage = "usage: table <faces> <column_height> [--multiplied] [--parallel] [out_path]";
+        let faces: u8 = args.get(2).expect(usage).parse().expect("faces must be a number");
+        let column_height: u8 = args.get(3).expect(usage).parse().expect("column_height must be a number");
+        assert!(
+            faces as usize <= Die::VALUES.len(),
+            "{}: faces must be at most {} (Die::VALUES)",
+            usage,
+            Die::VALUES.len()
+        );
+        assert!(
+            column_height <= 3,
+            "{}: column_height must be at most 3 (HandExplicit's slots)",
+            usage
+        );
+        let mut scoring = ScoringPolicy::SquaredPair;
+        let mut out_path = None;
+        let mut parallel = false;
+        for arg in &args[4..] {
+            match arg.as_str() {
+                "--multiplied" => scoring = ScoringPolicy::Multiplied,
+                "--parallel" => parallel = true,
+                path => out_path = Some(path),
+            }
+        }
+        let ruleset = RuleSet {
+            faces,
+            column_height,
+            scoring,
+        };
+        let result = match out_path {
+            Some(path) => emit_table(
+                &mut BufWriter::new(File::create(path).expect("failed to create output file")),
+                &ruleset,
+                parallel,
+            ),
+            None => emit_table(&mut io::stdout().lock(), &ruleset, parallel),
+        };
+        result.expect("failed to write table");
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("play") {
+        let usage = "usage: play [--multiplied] [seed]";
+        let mut scoring = ScoringPolicy::SquaredPair;
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        for arg in &args[2..] {
+            match arg.as_str() {
+                "--multiplied" => scoring = ScoringPolicy::Multiplied,
+                s => seed = s.parse().expect(usage),
+            }
+        }
+        play_game(
+            &RuleSet {
+                scoring,
+                ..RuleSet::classic()
+            },
+            seed,
+        );
+        return;
+    }
+
+    let parallel = args.get(1).map(String::as_str) == Some("--parallel");
+    let ruleset = RuleSet::classic();
+
+    let hands = hands_with(&ruleset);
     println!("Hands: {}", hands.len());
 
     let hand_pairs = hand_pairs(hands);
     println!("Hand pairs: {}", hand_pairs.len());
 
-    let state_counts = state_counts(hand_pairs);
+    let state_counts = if parallel {
+        state_counts_parallel_with(&hand_pairs, &ruleset)
+    } else {
+        state_counts_with(hand_pairs, &ruleset)
+    };
     println!("Intermediate states: {}", state_counts.0);
     println!("Final states: {}", state_counts.1);
     println!("Total: {}", state_counts.0 + state_counts.1);
 }
 
+// Minimal deterministic PRNG for `play_game` (no external `rand` crate is
+// available to this binary).
+struct XorShift64(u64);
+
+impl XorShift64 {
+    fn new(seed: u64) -> XorShift64 {
+        XorShift64(if seed == 0 { 1 } else { seed })
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    fn next_index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
+
+// A single move made by `play_out`: who moved, what they rolled and chose,
+// and the resulting board.
+struct Move {
+    player: u8,
+    die: Die,
+    column: u8,
+    state: State,
+}
+
+// Plays one full game of `ruleset` to completion, choosing a random legal
+// column for each randomly rolled die, and returns every move made. Kept
+// separate from `play_game`'s printing so the move sequence (in
+// particular, that `player` strictly alternates) can be asserted on
+// directly in tests.
+fn play_out(ruleset: &RuleSet, seed: u64, hand_pairs_index: &HandPairs) -> Vec<Move> {
+    let mut rng = XorShift64::new(seed);
+    let mut state = State::new_by_index(0, 0, 0);
+    let mut move_number = 0u32;
+    let mut moves_made = vec![];
+    while !state.is_done(hand_pairs_index) {
+        let player = State::turn(move_number);
+        let legal: Vec<u8> = state.legal_moves(hand_pairs_index, player).collect();
+        if player == 0 {
+            debug_assert_eq!(legal.len(), state.num_choices(hand_pairs_index) as usize);
+        }
+        let column = legal[rng.next_index(legal.len())];
+        let die = Die::VALUES[rng.next_index(ruleset.faces as usize)];
+        state = state.place(hand_pairs_index, column, die, player);
+        move_number += 1;
+        moves_made.push(Move { player, die, column, state });
+    }
+    moves_made
+}
+
+// Plays one full game of `ruleset` via `play_out` and prints every move and
+// the final scores, so `place`/`legal_moves`/`turn`/`num_choices` have a
+// real caller driving a game end-to-end, not just the solver's internal
+// search.
+fn play_game(ruleset: &RuleSet, seed: u64) {
+    let hand_pairs_index = HandPairs::new_with(*ruleset);
+    let moves = play_out(ruleset, seed, &hand_pairs_index);
+    for mv in &moves {
+        println!("player {} drops {} into column {}: {}", mv.player, mv.die, mv.column, mv.state.to_json());
+    }
+    let final_state = moves.last().map(|mv| mv.state).unwrap_or_else(|| State::new_by_index(0, 0, 0));
+    let (c1, c2, c3) = final_state.hands(&hand_pairs_index);
+    let score_0 = c1.0.score_with(ruleset) + c2.0.score_with(ruleset) + c3.0.score_with(ruleset);
+    let score_1 = c1.1.score_with(ruleset) + c2.1.score_with(ruleset) + c3.1.score_with(ruleset);
+    println!("final score: player 0 = {}, player 1 = {}", score_0, score_1);
+}
+
 fn hands() -> Vec<HandExplicit> {
+    hands_with(&RuleSet::classic())
+}
+
+// Every distinct hand reachable under `ruleset`'s face count and column
+// height (a subset of the 84 classic hands when either is smaller).
+fn hands_with(ruleset: &RuleSet) -> Vec<HandExplicit> {
+    let faces = &Die::VALUES[..ruleset.faces as usize];
     let mut v = vec![];
     v.push(HandExplicit::empty());
-    for (i1, d1) in Die::VALUES.iter().enumerate() {
+    if ruleset.column_height == 0 {
+        return v;
+    }
+    for (i1, d1) in faces.iter().enumerate() {
         v.push(HandExplicit::new1(*d1));
-        for (i2, d2) in Die::VALUES.iter().skip(i1).enumerate() {
+        if ruleset.column_height == 1 {
+            continue;
+        }
+        for (i2, d2) in faces.iter().skip(i1).enumerate() {
             v.push(HandExplicit::new2(*d1, *d2));
-            for d3 in Die::VALUES.iter().skip(i1 + i2) {
+            if ruleset.column_height == 2 {
+                continue;
+            }
+            for d3 in faces.iter().skip(i1 + i2) {
                 v.push(HandExplicit::new(*d1, *d2, *d3));
             }
         }
@@ -340,14 +1026,34 @@ fn hand_pairs(hands: Vec<HandExplicit>) -> Vec<(HandExplicit, HandExplicit)> {
     v
 }
 
-fn state_counts(hand_pairs: Vec<(HandExplicit, HandExplicit)>) -> (u64, u64) {
+fn state_counts_with(
+    hand_pairs: Vec<(HandExplicit, HandExplicit)>,
+    ruleset: &RuleSet,
+) -> (u64, u64) {
+    state_counts_in_range(&hand_pairs, 0..hand_pairs.len(), ruleset)
+}
+
+// Tallies states whose smallest column index (`ix_1`) falls in `ix_1_range`,
+// so callers can partition the outer loop across worker threads without
+// double-counting: every state is sorted by canonicalization, so it is
+// produced by exactly one `ix_1`.
+fn state_counts_in_range(
+    hand_pairs: &[(HandExplicit, HandExplicit)],
+    ix_1_range: std::ops::Range<usize>,
+    ruleset: &RuleSet,
+) -> (u64, u64) {
     let mut intermediate_states: u64 = 0;
     let mut final_states: u64 = 0;
-    for (ix_1, column_1) in hand_pairs.iter().enumerate() {
+    for ix_1 in ix_1_range {
+        let column_1 = &hand_pairs[ix_1];
         for (ix_2, column_2) in hand_pairs.iter().skip(ix_1).enumerate() {
             for column_3 in hand_pairs.iter().skip(ix_1 + ix_2) {
-                let p1_full = column_1.0.is_full() && column_2.0.is_full() && column_3.0.is_full();
-                let p2_full = column_1.1.is_full() && column_2.1.is_full() && column_3.1.is_full();
+                let p1_full = column_1.0.is_full_with(ruleset)
+                    && column_2.0.is_full_with(ruleset)
+                    && column_3.0.is_full_with(ruleset);
+                let p2_full = column_1.1.is_full_with(ruleset)
+                    && column_2.1.is_full_with(ruleset)
+                    && column_3.1.is_full_with(ruleset);
                 if p1_full && p2_full {
                     // Invalid state, can't have both sides full
                     continue;
@@ -362,3 +1068,256 @@ fn state_counts(hand_pairs: Vec<(HandExplicit, HandExplicit)>) -> (u64, u64) {
     }
     (intermediate_states, final_states)
 }
+
+// Same tally as `state_counts_with`, but splits the outer `ix_1` range
+// across worker threads (one contiguous slice each, smallest-index first
+// so no state is counted twice), summing their partial tallies. Available
+// alongside the sequential path for deterministic debugging.
+fn state_counts_parallel_with(
+    hand_pairs: &[(HandExplicit, HandExplicit)],
+    ruleset: &RuleSet,
+) -> (u64, u64) {
+    let thread_count = available_parallelism();
+    let chunk_size = hand_pairs.len().max(1).div_ceil(thread_count);
+    thread::scope(|scope| {
+        (0..hand_pairs.len())
+            .step_by(chunk_size.max(1))
+            .map(|start| {
+                let end = (start + chunk_size).min(hand_pairs.len());
+                scope.spawn(move || state_counts_in_range(hand_pairs, start..end, ruleset))
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().expect("worker thread panicked"))
+            .fold((0u64, 0u64), |(i, f), (di, df)| (i + di, f + df))
+    })
+}
+
+// Number of worker threads to use for the parallel paths: one per
+// available core, falling back to a single thread if that can't be read.
+fn available_parallelism() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+// Every canonical, non-double-full `State` reachable from `hand_pairs`
+// (same triple nested loop as `state_counts_in_range`, but building `State`s).
+fn states(hand_pairs: &[(HandExplicit, HandExplicit)], hand_pairs_index: &HandPairs) -> Vec<State> {
+    let ruleset = hand_pairs_index.ruleset();
+    let mut v = vec![];
+    for (ix_1, column_1) in hand_pairs.iter().enumerate() {
+        for (ix_2, column_2) in hand_pairs.iter().skip(ix_1).enumerate() {
+            for column_3 in hand_pairs.iter().skip(ix_1 + ix_2) {
+                let p1_full = column_1.0.is_full_with(&ruleset)
+                    && column_2.0.is_full_with(&ruleset)
+                    && column_3.0.is_full_with(&ruleset);
+                let p2_full = column_1.1.is_full_with(&ruleset)
+                    && column_2.1.is_full_with(&ruleset)
+                    && column_3.1.is_full_with(&ruleset);
+                if p1_full && p2_full {
+                    // Invalid state, can't have both sides full
+                    continue;
+                }
+                v.push(State::new(hand_pairs_index, column_1, column_2, column_3));
+            }
+        }
+    }
+    v
+}
+
+// Streams every enumerated hand, and every reachable state paired with its
+// solved best move and expected value for each possible die roll, as JSON.
+// `ruleset` must be given explicitly by the caller (rather than defaulting
+// to `RuleSet::classic()`): the classic ruleset's state space is billions
+// of states, which `solve`'s `HashMap<State, f64>` can't hold in memory, so
+// `main` requires `faces`/`column_height` small enough to be tractable.
+fn emit_table<W: Write>(out: &mut W, ruleset: &RuleSet, parallel: bool) -> io::Result<()> {
+    let hands = hands_with(ruleset);
+    write!(out, "{{\"hands\":[")?;
+    for (ix, hand) in hands.iter().enumerate() {
+        if ix > 0 {
+            write!(out, ",")?;
+        }
+        write!(out, "{}", hand.to_json())?;
+    }
+    write!(out, "],\"states\":[")?;
+
+    let hand_pairs = hand_pairs(hands);
+    let hand_pairs_index = HandPairs::new_with(*ruleset);
+    let all_states = states(&hand_pairs, &hand_pairs_index);
+    let mut solver = Solver::new(&hand_pairs_index);
+    if parallel {
+        solver.solve_parallel(&all_states);
+    } else {
+        solver.solve(&all_states);
+    }
+    for (ix, state) in all_states.iter().enumerate() {
+        if ix > 0 {
+            write!(out, ",")?;
+        }
+        write!(out, "{{\"columns\":{},\"moves\":[", state.to_json())?;
+        // Terminal states (one side already full) end the game, so no move is
+        // emitted for them; where player 0's own side is the full one,
+        // `decision` would also return its NEG_INFINITY initial value.
+        if !state.is_done(&hand_pairs_index) {
+            for (i, die) in Die::VALUES[..ruleset.faces as usize].iter().enumerate() {
+                if i > 0 {
+                    write!(out, ",")?;
+                }
+                write!(
+                    out,
+                    "{{\"die\":{},\"best_column\":{},\"expected_value\":{}}}",
+                    *die as u8,
+                    solver.best_move(state, *die),
+                    solver.expected_value(state, *die),
+                )?;
+            }
+        }
+        write!(out, "]}}")?;
+    }
+    writeln!(out, "]}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turn_alternates_despite_destruction() {
+        let ruleset = RuleSet::classic();
+        let hand_pairs_index = HandPairs::new_with(ruleset);
+        for seed in [1, 4, 10, 42] {
+            let moves = play_out(&ruleset, seed, &hand_pairs_index);
+            for (a, b) in moves.iter().zip(moves.iter().skip(1)) {
+                assert_ne!(a.player, b.player, "same player moved twice in a row for seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn solve_reaches_bellman_fixed_point() {
+        let ruleset = RuleSet {
+            faces: 2,
+            column_height: 1,
+            scoring: ScoringPolicy::SquaredPair,
+        };
+        let hand_pairs_index = HandPairs::new_with(ruleset);
+        let all_states = states(&hand_pairs(hands_with(&ruleset)), &hand_pairs_index);
+
+        let mut solver = Solver::new(&hand_pairs_index);
+        solver.solve(&all_states);
+
+        for state in &all_states {
+            if state.is_done(&hand_pairs_index) {
+                continue;
+            }
+            let value = solver.value(state);
+            let chance_value = solver.chance_value(state);
+            assert!(
+                (value - chance_value).abs() < SOLVE_EPSILON * 10.0,
+                "{:?}: value {} != chance_value {}",
+                state,
+                value,
+                chance_value
+            );
+        }
+    }
+
+    #[test]
+    fn place_destroys_matching_opponent_dice() {
+        let hand_pairs_index = HandPairs::new();
+        let target = (HandExplicit::empty(), HandExplicit::new2(Die::THREE, Die::THREE));
+        let empty_pair = (HandExplicit::empty(), HandExplicit::empty());
+        let state = State::new(&hand_pairs_index, &target, &empty_pair, &empty_pair);
+
+        let (c1, c2, c3) = state.hands(&hand_pairs_index);
+        let column_ix = [c1, c2, c3]
+            .iter()
+            .position(|c| c.1.has(Die::THREE))
+            .expect("target column present") as u8;
+
+        let after = state.place(&hand_pairs_index, column_ix, Die::THREE, 0);
+
+        let (c1, c2, c3) = after.hands(&hand_pairs_index);
+        let updated = [c1, c2, c3]
+            .into_iter()
+            .find(|c| c.0.has(Die::THREE))
+            .expect("die placed on player 0's side");
+        assert_eq!(updated.0, HandExplicit::new1(Die::THREE));
+        assert_eq!(updated.1.len(), 0, "matching opponent dice should be removed");
+    }
+
+    #[test]
+    fn rank_unrank_round_trip() {
+        let ruleset = RuleSet::classic();
+        for ix in 0..hand_count_with(&ruleset) {
+            let hand = HandExplicit::unrank_with(&ruleset, ix as u16);
+            assert_eq!(hand.rank_with(&ruleset), ix as u16);
+        }
+    }
+
+    #[test]
+    fn hand_pairs_round_trip() {
+        let hand_pairs_index = HandPairs::new();
+        let hands = hands();
+        let pair_count = hand_pairs(hands.clone()).len();
+        for ix in 0..pair_count as u16 {
+            let pair = hand_pairs_index.get_by_index(ix);
+            assert!(!overlaps(&pair.0, &pair.1));
+            assert_eq!(hand_pairs_index.get_by_hand(&pair), ix);
+        }
+    }
+
+    #[test]
+    fn hand_pairs_round_trip_shrunk_ruleset() {
+        let ruleset = RuleSet {
+            faces: 3,
+            column_height: 2,
+            scoring: ScoringPolicy::SquaredPair,
+        };
+        let hand_pairs_index = HandPairs::new_with(ruleset);
+        let pair_count = hand_pairs(hands_with(&ruleset)).len();
+        for ix in 0..pair_count as u16 {
+            let pair = hand_pairs_index.get_by_index(ix);
+            assert!(!overlaps(&pair.0, &pair.1));
+            assert_eq!(hand_pairs_index.get_by_hand(&pair), ix);
+        }
+    }
+
+    #[test]
+    fn state_counts_parallel_matches_sequential() {
+        let ruleset = RuleSet {
+            faces: 3,
+            column_height: 2,
+            scoring: ScoringPolicy::SquaredPair,
+        };
+        let hand_pairs = hand_pairs(hands_with(&ruleset));
+        let sequential = state_counts_with(hand_pairs.clone(), &ruleset);
+        let parallel = state_counts_parallel_with(&hand_pairs, &ruleset);
+        assert_eq!(sequential, parallel);
+    }
+
+    #[test]
+    fn solve_parallel_matches_sequential() {
+        let ruleset = RuleSet {
+            faces: 2,
+            column_height: 1,
+            scoring: ScoringPolicy::SquaredPair,
+        };
+        let hand_pairs_index = HandPairs::new_with(ruleset);
+        let all_states = states(&hand_pairs(hands_with(&ruleset)), &hand_pairs_index);
+
+        let mut sequential = Solver::new(&hand_pairs_index);
+        sequential.solve(&all_states);
+
+        let mut parallel = Solver::new(&hand_pairs_index);
+        parallel.solve_parallel(&all_states);
+
+        for state in &all_states {
+            let a = sequential.values.get(state).copied().unwrap_or(0.0);
+            let b = parallel.values.get(state).copied().unwrap_or(0.0);
+            assert!((a - b).abs() < 1e-6, "{:?}: {} vs {}", state, a, b);
+        }
+    }
+}